@@ -0,0 +1,431 @@
+//! Structured command parsing on top of the flat [`Lexer`](crate::lexer): pipelines (`|`), sequences (`;`), and
+//! the short-circuiting boolean operators `&&`/`||`, with standard shell precedence (pipelines bind tighter than
+//! `&&`/`||`, which bind tighter than `;`).
+
+use crate::{
+    lexer::{Lexer, Token},
+    Error,
+};
+use std::iter::Peekable;
+
+/// A redirection attached to a [`Command::Simple`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Redirection<'a> {
+    /// `>target` or `> target`: truncate-and-write `target`.
+    Output(&'a str),
+    /// `>>target` or `>> target`: append to `target`.
+    Append(&'a str),
+    /// `<target` or `< target`: read from `target`.
+    Input(&'a str),
+}
+
+/// A parsed command, possibly composed of several simple commands joined by pipelines, sequences, or the
+/// short-circuiting boolean operators.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Command<'a> {
+    /// A single command, its arguments, and any redirections, e.g. `grep foo < in.txt > out.txt`.
+    Simple { words: Vec<&'a str>, redirections: Vec<Redirection<'a>> },
+    /// `a | b | c`: the standard output of each command feeds the standard input of the next.
+    Pipeline(Vec<Command<'a>>),
+    /// `a ; b ; c`: run each command in turn, regardless of the previous command's result.
+    Sequence(Vec<Command<'a>>),
+    /// `a && b && c`: run each command in turn, stopping at the first failure.
+    ShortCircuitConjunction(Vec<Command<'a>>),
+    /// `a || b || c`: run each command in turn, stopping at the first success.
+    ShortCircuitDisjunction(Vec<Command<'a>>),
+}
+
+/// Parses a single input string into a [Command](Command) tree.
+///
+/// ```rust
+/// # use shtring::{Command, CommandParser};
+/// let command = CommandParser::new("grep foo | sort > out.txt").parse().unwrap();
+/// assert_eq!(
+///     command,
+///     Command::Pipeline(vec![
+///         Command::Simple { words: vec!["grep", "foo"], redirections: vec![] },
+///         Command::Simple { words: vec!["sort"], redirections: vec![shtring::Redirection::Output("out.txt")] },
+///     ])
+/// );
+/// ```
+#[derive(Debug)]
+pub struct CommandParser<'a> {
+    input: &'a str,
+    lexer: Peekable<Lexer<'a>>,
+}
+
+impl<'a> CommandParser<'a> {
+    /// Return a new [CommandParser](CommandParser) over a given input string.
+    pub fn new(input: &'a str) -> Self {
+        Self { input, lexer: Lexer::with_operators(input).peekable() }
+    }
+
+    /// Parse the whole input string into a single [Command](Command), returning the first encountered error, if
+    /// any.
+    pub fn parse(mut self) -> Result<Command<'a>, Error<'a>> {
+        self.skip_whitespace();
+        let command = self.parse_sequence()?;
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(command),
+            Some(Ok((idx, token))) => Err(Error::UnexpectedToken(idx, &self.input[idx..idx + token.len()])),
+            Some(Err(e)) => Err(e),
+        }
+    }
+
+    fn peek(&mut self) -> Option<Result<(usize, Token<'a>), Error<'a>>> {
+        self.lexer.peek().copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(Ok((_, Token::Whitespace(_))))) {
+            self.lexer.next();
+        }
+    }
+
+    /// `sequence := boolean_chain (';' boolean_chain)*`
+    fn parse_sequence(&mut self) -> Result<Command<'a>, Error<'a>> {
+        let mut commands = vec![self.parse_boolean_chain()?];
+        loop {
+            self.skip_whitespace();
+            if !matches!(self.peek(), Some(Ok((_, Token::Semicolon)))) {
+                break;
+            }
+            self.lexer.next();
+            self.skip_whitespace();
+            if self.peek().is_none() {
+                break;
+            }
+            commands.push(self.parse_boolean_chain()?);
+        }
+        Ok(collapse(commands, Command::Sequence))
+    }
+
+    /// `boolean_chain := pipeline (('&&' pipeline)+ | ('||' pipeline)+)*`, left-associative and grouped by runs of
+    /// the same operator.
+    fn parse_boolean_chain(&mut self) -> Result<Command<'a>, Error<'a>> {
+        let mut current = self.parse_pipeline()?;
+        loop {
+            self.skip_whitespace();
+            let is_and = match self.peek() {
+                Some(Ok((_, Token::And))) => true,
+                Some(Ok((_, Token::Or))) => false,
+                _ => break,
+            };
+            let mut group = vec![current];
+            loop {
+                self.lexer.next();
+                self.skip_whitespace();
+                group.push(self.parse_pipeline()?);
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(Ok((_, Token::And))) if is_and => continue,
+                    Some(Ok((_, Token::Or))) if !is_and => continue,
+                    _ => break,
+                }
+            }
+            current = if is_and { Command::ShortCircuitConjunction(group) } else { Command::ShortCircuitDisjunction(group) };
+        }
+        Ok(current)
+    }
+
+    /// `pipeline := simple ('|' simple)*`
+    fn parse_pipeline(&mut self) -> Result<Command<'a>, Error<'a>> {
+        let mut commands = vec![self.parse_simple()?];
+        loop {
+            self.skip_whitespace();
+            if !matches!(self.peek(), Some(Ok((_, Token::Pipe)))) {
+                break;
+            }
+            self.lexer.next();
+            self.skip_whitespace();
+            commands.push(self.parse_simple()?);
+        }
+        Ok(collapse(commands, Command::Pipeline))
+    }
+
+    /// `simple := (word | redirection)+`, terminated by `|`, `&&`, `||`, `;`, or the end of input. `>`, `>>`, and
+    /// `<` are lexed as dedicated operator tokens (see [Lexer::with_operators](crate::lexer::Lexer::with_operators)),
+    /// so they're recognised as redirections whether spaced (`> out.txt`), attached to their target (`>out.txt`),
+    /// or attached to the preceding word (`ls>out.txt`). A quoted word is never tokenized as one of those
+    /// operators, so e.g. `echo ">"` stays the literal argument `>` rather than a dangling output redirection.
+    fn parse_simple(&mut self) -> Result<Command<'a>, Error<'a>> {
+        let mut words = Vec::new();
+        let mut redirections = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let build = match self.peek() {
+                None | Some(Ok((_, Token::Pipe | Token::And | Token::Or | Token::Semicolon))) => break,
+                Some(Err(e)) => return Err(e),
+                Some(Ok((_, Token::Greater))) => Some(Redirection::Output as fn(&'a str) -> Redirection<'a>),
+                Some(Ok((_, Token::GreaterGreater))) => Some(Redirection::Append as fn(&'a str) -> Redirection<'a>),
+                Some(Ok((_, Token::Less))) => Some(Redirection::Input as fn(&'a str) -> Redirection<'a>),
+                _ => None,
+            };
+            if let Some(build) = build {
+                let idx = match self.lexer.next() {
+                    Some(Ok((idx, _))) => idx,
+                    Some(Err(e)) => return Err(e),
+                    None => unreachable!("peek just confirmed a token"),
+                };
+                self.skip_whitespace();
+                let target = self.next_word()?.map(|(_, word, _)| word).ok_or(Error::UnexpectedEndOfInput(idx))?;
+                redirections.push(build(target));
+                continue;
+            }
+            match self.next_word()? {
+                Some((_, word, _)) => words.push(word),
+                None => break,
+            }
+        }
+        if words.is_empty() && redirections.is_empty() {
+            return Err(Error::UnexpectedEndOfInput(self.input.len()));
+        }
+        Ok(Command::Simple { words, redirections })
+    }
+
+    /// Parse the next word-like argument (reusing the same quote/escape rules as [Parser](crate::Parser)),
+    /// returning `None` once an operator token or the end of input is reached without consuming it. On success,
+    /// the returned triple is the word's own starting byte offset, its text, and whether it came from a quoted
+    /// span (`'...'`/`"..."`) rather than a bare/escaped token.
+    fn next_word(&mut self) -> Result<Option<(usize, &'a str, bool)>, Error<'a>> {
+        match self.peek() {
+            None
+            | Some(Ok((
+                _,
+                Token::Pipe | Token::And | Token::Or | Token::Semicolon | Token::Greater | Token::GreaterGreater | Token::Less,
+            ))) => return Ok(None),
+            Some(Err(e)) => return Err(e),
+            _ => {}
+        }
+        match self.lexer.next() {
+            Some(Ok((idx, Token::Word(_) | Token::UnknownCharacter(_) | Token::Escape(_)))) => {
+                Ok(Some((idx, self.collect_word(idx)?, false)))
+            }
+            Some(Ok((idx, token @ (Token::SingleQuote | Token::DoubleQuote)))) => {
+                Ok(Some((idx, self.collect_quoted(idx, token)?, true)))
+            }
+            Some(Ok((idx, token))) => Err(Error::UnexpectedToken(idx, &self.input[idx..idx + token.len()])),
+            Some(Err(e)) => Err(e),
+            None => unreachable!("end of input was already handled above"),
+        }
+    }
+
+    fn collect_word(&mut self, idx: usize) -> Result<&'a str, Error<'a>> {
+        loop {
+            match self.peek() {
+                Some(Ok((
+                    cont,
+                    Token::Whitespace(_)
+                    | Token::Pipe
+                    | Token::And
+                    | Token::Or
+                    | Token::Semicolon
+                    | Token::Greater
+                    | Token::GreaterGreater
+                    | Token::Less,
+                ))) => return Ok(&self.input[idx..cont]),
+                Some(Ok((_, Token::Word(_) | Token::UnknownCharacter(_) | Token::Escape(_)))) => {
+                    self.lexer.next();
+                }
+                Some(Ok((cont, token))) => {
+                    return Err(Error::UnexpectedToken(cont, &self.input[cont..cont + token.len()]))
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Ok(&self.input[idx..]),
+            }
+        }
+    }
+
+    fn collect_quoted(&mut self, idx: usize, opening: Token<'a>) -> Result<&'a str, Error<'a>> {
+        loop {
+            match self.lexer.next() {
+                Some(Ok((cont, token))) if token == opening => return Ok(&self.input[idx + 1..cont]),
+                Some(Ok(_)) => continue,
+                Some(Err(Error::UnexpectedEndOfInput(_))) | None => return Err(Error::UnexpectedEndOfInput(idx)),
+                Some(Err(e)) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Collapse a single-element group down to its only command, otherwise wrap the group with `build`.
+fn collapse<'a>(mut commands: Vec<Command<'a>>, build: impl FnOnce(Vec<Command<'a>>) -> Command<'a>) -> Command<'a> {
+    if commands.len() == 1 {
+        commands.pop().expect("length was just checked to be 1")
+    } else {
+        build(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple(words: Vec<&str>) -> Command<'_> {
+        Command::Simple { words, redirections: vec![] }
+    }
+
+    #[test]
+    fn single_simple_command() {
+        let command = CommandParser::new("ls -la").parse();
+        assert_eq!(command, Ok(simple(vec!["ls", "-la"])));
+    }
+
+    #[test]
+    fn pipeline() {
+        let command = CommandParser::new("cat foo | grep bar | sort").parse();
+        assert_eq!(
+            command,
+            Ok(Command::Pipeline(vec![
+                simple(vec!["cat", "foo"]),
+                simple(vec!["grep", "bar"]),
+                simple(vec!["sort"]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn sequence() {
+        let command = CommandParser::new("a ; b ; c").parse();
+        assert_eq!(command, Ok(Command::Sequence(vec![simple(vec!["a"]), simple(vec!["b"]), simple(vec!["c"])])));
+    }
+
+    #[test]
+    fn conjunction() {
+        let command = CommandParser::new("a && b && c").parse();
+        assert_eq!(
+            command,
+            Ok(Command::ShortCircuitConjunction(vec![simple(vec!["a"]), simple(vec!["b"]), simple(vec!["c"])]))
+        );
+    }
+
+    #[test]
+    fn disjunction() {
+        let command = CommandParser::new("a || b").parse();
+        assert_eq!(command, Ok(Command::ShortCircuitDisjunction(vec![simple(vec!["a"]), simple(vec!["b"])])));
+    }
+
+    #[test]
+    fn pipelines_bind_tighter_than_boolean_operators() {
+        let command = CommandParser::new("a | b && c").parse();
+        assert_eq!(
+            command,
+            Ok(Command::ShortCircuitConjunction(vec![
+                Command::Pipeline(vec![simple(vec!["a"]), simple(vec!["b"])]),
+                simple(vec!["c"]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn boolean_operators_bind_tighter_than_sequences() {
+        let command = CommandParser::new("a && b ; c").parse();
+        assert_eq!(
+            command,
+            Ok(Command::Sequence(vec![
+                Command::ShortCircuitConjunction(vec![simple(vec!["a"]), simple(vec!["b"])]),
+                simple(vec!["c"]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn mixed_boolean_operators_group_by_run() {
+        let command = CommandParser::new("a && b || c").parse();
+        assert_eq!(
+            command,
+            Ok(Command::ShortCircuitDisjunction(vec![
+                Command::ShortCircuitConjunction(vec![simple(vec!["a"]), simple(vec!["b"])]),
+                simple(vec!["c"]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn adjacent_operators_without_whitespace() {
+        let command = CommandParser::new("a&&b").parse();
+        assert_eq!(command, Ok(Command::ShortCircuitConjunction(vec![simple(vec!["a"]), simple(vec!["b"])])));
+    }
+
+    #[test]
+    fn attached_redirections() {
+        let command = CommandParser::new("sort <in.txt >>out.txt").parse();
+        assert_eq!(
+            command,
+            Ok(Command::Simple {
+                words: vec!["sort"],
+                redirections: vec![Redirection::Input("in.txt"), Redirection::Append("out.txt")],
+            })
+        );
+    }
+
+    #[test]
+    fn spaced_redirections() {
+        let command = CommandParser::new("sort < in.txt > out.txt").parse();
+        assert_eq!(
+            command,
+            Ok(Command::Simple {
+                words: vec!["sort"],
+                redirections: vec![Redirection::Input("in.txt"), Redirection::Output("out.txt")],
+            })
+        );
+    }
+
+    #[test]
+    fn quoting_still_groups_simple_command_words() {
+        let command = CommandParser::new("echo \"a b\" c").parse();
+        assert_eq!(command, Ok(simple(vec!["echo", "a b", "c"])));
+    }
+
+    #[test]
+    fn quoted_redirection_operator_stays_a_literal_word() {
+        let command = CommandParser::new("echo \">\" foo").parse();
+        assert_eq!(command, Ok(simple(vec!["echo", ">", "foo"])));
+    }
+
+    #[test]
+    fn lone_quoted_redirection_operator_is_not_an_error() {
+        let command = CommandParser::new("echo \">\"").parse();
+        assert_eq!(command, Ok(simple(vec!["echo", ">"])));
+    }
+
+    #[test]
+    fn quoted_attached_redirection_stays_a_literal_word() {
+        let command = CommandParser::new("echo \">x\"").parse();
+        assert_eq!(command, Ok(simple(vec!["echo", ">x"])));
+    }
+
+    #[test]
+    fn redirection_fused_to_the_end_of_the_preceding_word() {
+        let command = CommandParser::new("ls>out.txt").parse();
+        assert_eq!(
+            command,
+            Ok(Command::Simple { words: vec!["ls"], redirections: vec![Redirection::Output("out.txt")] })
+        );
+    }
+
+    #[test]
+    fn redirection_fused_between_two_words() {
+        let command = CommandParser::new("sort file.txt>out.txt").parse();
+        assert_eq!(
+            command,
+            Ok(Command::Simple {
+                words: vec!["sort", "file.txt"],
+                redirections: vec![Redirection::Output("out.txt")],
+            })
+        );
+    }
+
+    #[test]
+    fn trailing_operator_is_an_error() {
+        let command = CommandParser::new("a |").parse();
+        assert_eq!(command, Err(Error::UnexpectedEndOfInput(3)));
+    }
+
+    #[test]
+    fn unterminated_quote_is_an_error() {
+        let command = CommandParser::new("a \"b").parse();
+        assert_eq!(command, Err(Error::UnexpectedEndOfInput(2)));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    parser::{Parser, Quoting},
+    segment::{ParameterFormat, SegmentLexer, Word, WordSegment},
+    Error,
+};
+use std::borrow::Cow;
+
+/// Iterator over the arguments in an input string, substituting `$NAME` and `${NAME...}` parameter references
+/// against a caller-supplied resolver as it parses.
+///
+/// Returned by [Parser::with_expansion](crate::Parser::with_expansion). Expansion runs inside unquoted words and
+/// double-quoted strings; single-quoted text is left untouched, matching shell semantics. The brace forms
+/// `${NAME}`, `${NAME:-default}`, `${NAME:=assign}`, `${NAME:+alternate}`, and `${NAME:?error}` are supported,
+/// where `default`/`assign`/`error` substitute in place of `NAME` when the resolver returns `None` for it, and
+/// `alternate` substitutes in place of `NAME` when the resolver returns `Some`. A reference with no default that
+/// resolves to `None` expands to an empty string.
+///
+/// ```rust
+/// # use shtring::Parser;
+/// # use std::borrow::Cow;
+/// let input = "$GREETING, '$NAME' ${MISSING:-stranger}";
+/// let resolve = |name: &str| match name {
+///     "GREETING" => Some("Hello".to_string()),
+///     "NAME" => Some("World".to_string()),
+///     _ => None,
+/// };
+/// let mut parser = Parser::new(input).with_expansion(resolve);
+/// assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("Hello,".to_string()))));
+/// assert_eq!(parser.next(), Some(Ok(Cow::Borrowed("$NAME"))));
+/// assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("stranger".to_string()))));
+/// assert_eq!(parser.next(), None);
+/// ```
+#[derive(Debug)]
+pub struct ExpandingParser<'a, F> {
+    parser: Parser<'a>,
+    resolve: F,
+}
+
+impl<'a, F> ExpandingParser<'a, F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    pub(crate) fn new(input: &'a str, resolve: F) -> Self {
+        Self { parser: Parser::new(input), resolve }
+    }
+
+    fn expand(&self, raw: &'a str) -> Cow<'a, str> {
+        expand_word(&SegmentLexer::new(raw).parse(), &self.resolve)
+    }
+}
+
+impl<'a, F> Iterator for ExpandingParser<'a, F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    type Item = Result<Cow<'a, str>, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next_quoted().map(|r| {
+            r.map(|(quoting, raw, _)| match quoting {
+                Quoting::Single => Cow::Borrowed(raw),
+                Quoting::Bare | Quoting::Double => self.expand(raw),
+            })
+        })
+    }
+}
+
+/// Render a parsed `Word` to its expanded form, borrowing the original text when it contains no parameter
+/// references.
+fn expand_word<'a>(word: &Word<'a>, resolve: &impl Fn(&str) -> Option<String>) -> Cow<'a, str> {
+    if let [WordSegment::Literal(lit)] = word.0.as_slice() {
+        return Cow::Borrowed(lit);
+    }
+
+    let mut expanded = String::new();
+    for segment in &word.0 {
+        match segment {
+            WordSegment::Literal(lit) => expanded.push_str(lit),
+            WordSegment::Parameter(parameter) => {
+                let value = resolve(parameter.name);
+                match (&parameter.format, value) {
+                    (Some(ParameterFormat::Alternate(alternate)), Some(_)) => {
+                        expanded.push_str(&expand_word(alternate, resolve))
+                    }
+                    (Some(ParameterFormat::Alternate(_)), None) => {}
+                    (_, Some(value)) => expanded.push_str(&value),
+                    (
+                        Some(
+                            ParameterFormat::Default(default)
+                            | ParameterFormat::Assign(default)
+                            | ParameterFormat::Error(default),
+                        ),
+                        None,
+                    ) => expanded.push_str(&expand_word(default, resolve)),
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+    Cow::Owned(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    fn resolve(name: &str) -> Option<String> {
+        match name {
+            "NAME" => Some("World".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn unquoted_word_is_expanded() {
+        let input = "Hello $NAME!";
+        let mut parser = Parser::new(input).with_expansion(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::Borrowed("Hello"))));
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("World!".to_string()))));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn double_quoted_word_is_expanded() {
+        let input = "\"Hello $NAME!\"";
+        let mut parser = Parser::new(input).with_expansion(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("Hello World!".to_string()))));
+    }
+
+    #[test]
+    fn single_quoted_word_is_not_expanded() {
+        let input = "'Hello $NAME!'";
+        let mut parser = Parser::new(input).with_expansion(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::Borrowed("Hello $NAME!"))));
+    }
+
+    #[test]
+    fn unresolved_without_default_expands_to_empty() {
+        let input = "$MISSING";
+        let mut parser = Parser::new(input).with_expansion(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned(String::new()))));
+    }
+
+    #[test]
+    fn unresolved_with_default_expands_default() {
+        let input = "${MISSING:-fallback}";
+        let mut parser = Parser::new(input).with_expansion(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("fallback".to_string()))));
+    }
+
+    #[test]
+    fn resolved_alternate_expands_alternate() {
+        let input = "${NAME:+override}";
+        let mut parser = Parser::new(input).with_expansion(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("override".to_string()))));
+    }
+
+    #[test]
+    fn unresolved_alternate_expands_to_empty() {
+        let input = "${MISSING:+override}";
+        let mut parser = Parser::new(input).with_expansion(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned(String::new()))));
+    }
+}
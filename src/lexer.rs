@@ -1,10 +1,18 @@
 use crate::Error;
 use std::{fmt, fmt::Display, iter::Peekable, str::CharIndices};
 
+/// How deeply `$(...)` command substitutions may nest before [Lexer](Lexer) gives up, guarding against unbounded
+/// recursion on adversarial input.
+pub(crate) const DEFAULT_MAX_SUBSTITUTION_DEPTH: usize = 32;
+
 #[derive(Debug)]
 pub(crate) struct Lexer<'a> {
     input: &'a str,
     chars: Peekable<CharIndices<'a>>,
+    /// Whether `|`, `&&`, `||`, `;`, `>`, `>>`, and `<` are tokenized as the dedicated operator tokens below
+    /// rather than as ordinary word characters. Only [CommandParser](crate::CommandParser) turns this on; every
+    /// other mode treats them as plain text, unchanged from before those tokens existed.
+    operators: bool,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -14,6 +22,20 @@ pub(crate) enum Token<'a> {
     SingleQuote,
     DoubleQuote,
     Escape(&'a str),
+    /// `|`: pipeline separator.
+    Pipe,
+    /// `||`: short-circuiting disjunction.
+    Or,
+    /// `&&`: short-circuiting conjunction.
+    And,
+    /// `;`: command sequence separator.
+    Semicolon,
+    /// `>`: output redirection.
+    Greater,
+    /// `>>`: append redirection.
+    GreaterGreater,
+    /// `<`: input redirection.
+    Less,
     UnknownCharacter(char),
 }
 
@@ -22,7 +44,8 @@ impl Token<'_> {
         match self {
             Token::Word(w) | Token::Whitespace(w) | Token::Escape(w) => w.len(),
             Token::UnknownCharacter(c) => c.len_utf8(),
-            Token::SingleQuote | Token::DoubleQuote => 1,
+            Token::SingleQuote | Token::DoubleQuote | Token::Pipe | Token::Semicolon | Token::Greater | Token::Less => 1,
+            Token::Or | Token::And | Token::GreaterGreater => 2,
         }
     }
 }
@@ -34,15 +57,71 @@ impl Display for Token<'_> {
             Token::UnknownCharacter(c) => write!(f, "{}", c),
             Token::SingleQuote => write!(f, "'"),
             Token::DoubleQuote => write!(f, "\""),
+            Token::Pipe => write!(f, "|"),
+            Token::Or => write!(f, "||"),
+            Token::And => write!(f, "&&"),
+            Token::Semicolon => write!(f, ";"),
+            Token::Greater => write!(f, ">"),
+            Token::GreaterGreater => write!(f, ">>"),
+            Token::Less => write!(f, "<"),
         }
     }
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
-        Self {
-            input,
-            chars: input.char_indices().peekable(),
+        Self { input, chars: input.char_indices().peekable(), operators: false }
+    }
+
+    /// Like [Lexer::new], but also tokenizes `|`, `&&`, `||`, `;`, `>`, `>>`, and `<` as the dedicated operator
+    /// tokens used by [CommandParser](crate::CommandParser), instead of treating them as ordinary word characters.
+    pub fn with_operators(input: &'a str) -> Self {
+        Self { operators: true, ..Self::new(input) }
+    }
+
+    /// Whether the characters starting at the current position form `$(`, without consuming them.
+    fn peek_starts_substitution(&self) -> bool {
+        let mut lookahead = self.chars.clone();
+        matches!(lookahead.next(), Some((_, '$'))) && matches!(lookahead.next(), Some((_, '(')))
+    }
+
+    /// Whether `c` may appear inside a [Token::Word], which in operator mode excludes the `|`, `&`, `;`, `>`, and
+    /// `<` characters that start dedicated operator tokens there.
+    fn is_word_character(&self, c: char) -> bool {
+        let plain = c != '\'' && c != '"' && c != '\\' && !c.is_whitespace();
+        plain && (!self.operators || !matches!(c, '|' | '&' | ';' | '>' | '<'))
+    }
+
+    /// Consume through the `)` that closes the `$(` whose `(` was just consumed, returning its byte index.
+    /// Recurses one level deeper for every nested `$(` encountered along the way, so that e.g. the inner `)` in
+    /// `$(echo $(whoami))` closes the inner substitution rather than the outer one. `start` is the index of the
+    /// outermost `$`, used to report which substitution was too deep; `depth` is the nesting level being scanned.
+    fn consume_balanced_substitution(&mut self, start: usize, depth: usize) -> Result<usize, Error<'a>> {
+        if depth > DEFAULT_MAX_SUBSTITUTION_DEPTH {
+            return Err(Error::SubstitutionTooDeep(start));
+        }
+        loop {
+            match self.chars.peek().copied() {
+                Some((idx, ')')) => {
+                    self.chars.next();
+                    return Ok(idx);
+                }
+                Some((_, '\\')) => {
+                    self.chars.next();
+                    if self.chars.next().is_none() {
+                        return Err(Error::UnexpectedEndOfInput(start));
+                    }
+                }
+                Some((_, '$')) if self.peek_starts_substitution() => {
+                    self.chars.next();
+                    self.chars.next();
+                    self.consume_balanced_substitution(start, depth + 1)?;
+                }
+                Some(_) => {
+                    self.chars.next();
+                }
+                None => return Err(Error::UnexpectedEndOfInput(start)),
+            }
         }
     }
 }
@@ -57,8 +136,31 @@ impl<'a> Iterator for Lexer<'a> {
                 '"' => Some(Ok((idx, Token::DoubleQuote))),
                 '\\' => match self.chars.next() {
                     Some((cont, _)) => Some(Ok((idx, Token::Escape(&self.input[(idx..cont + 1)])))),
-                    None => Some(Err(Error::UnexpectedEndOfInput)),
+                    None => Some(Err(Error::UnexpectedEndOfInput(idx))),
+                },
+                '|' if self.operators => match self.chars.peek() {
+                    Some((_, '|')) => {
+                        self.chars.next();
+                        Some(Ok((idx, Token::Or)))
+                    }
+                    _ => Some(Ok((idx, Token::Pipe))),
+                },
+                '&' if self.operators => match self.chars.peek() {
+                    Some((_, '&')) => {
+                        self.chars.next();
+                        Some(Ok((idx, Token::And)))
+                    }
+                    _ => Some(Ok((idx, Token::UnknownCharacter('&')))),
                 },
+                ';' if self.operators => Some(Ok((idx, Token::Semicolon))),
+                '>' if self.operators => match self.chars.peek() {
+                    Some((_, '>')) => {
+                        self.chars.next();
+                        Some(Ok((idx, Token::GreaterGreater)))
+                    }
+                    _ => Some(Ok((idx, Token::Greater))),
+                },
+                '<' if self.operators => Some(Ok((idx, Token::Less))),
                 c if c.is_whitespace() => {
                     let mut end = idx;
                     loop {
@@ -70,11 +172,29 @@ impl<'a> Iterator for Lexer<'a> {
                     }
                     Some(Ok((idx, Token::Whitespace(&self.input[(idx..end + 1)]))))
                 }
-                c if is_word_character(c) => {
+                c if self.is_word_character(c) => {
                     let mut end = idx;
+                    if chr == '$' && matches!(self.chars.peek(), Some((_, '('))) {
+                        self.chars.next();
+                        match self.consume_balanced_substitution(idx, 1) {
+                            Ok(close) => end = close,
+                            Err(e) => return Some(Err(e)),
+                        }
+                    }
                     loop {
-                        match self.chars.peek() {
-                            Some((cont, c)) if is_word_character(*c) => end = *cont,
+                        if self.peek_starts_substitution() {
+                            let (dollar, _) = self.chars.next().expect("lookahead just saw `$`");
+                            self.chars.next();
+                            match self.consume_balanced_substitution(dollar, 1) {
+                                Ok(close) => {
+                                    end = close;
+                                    continue;
+                                }
+                                Err(e) => return Some(Err(e)),
+                            }
+                        }
+                        match self.chars.peek().copied() {
+                            Some((cont, c)) if self.is_word_character(c) => end = cont,
                             _ => break,
                         }
                         self.chars.next();
@@ -88,10 +208,6 @@ impl<'a> Iterator for Lexer<'a> {
     }
 }
 
-fn is_word_character(c: char) -> bool {
-    c != '\'' && c != '"' && c != '\\' && !c.is_whitespace()
-}
-
 #[cfg(test)]
 mod tests {
     extern crate test;
@@ -160,7 +276,136 @@ mod tests {
         let input = r"\";
         let mut lexer = Lexer::new(input);
         let output = lexer.next();
-        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput)));
+        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput(0))));
+    }
+
+    #[test]
+    fn pipe_token() {
+        let input = "|";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Pipe))));
+    }
+
+    #[test]
+    fn or_token() {
+        let input = "||";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Or))));
+    }
+
+    #[test]
+    fn and_token() {
+        let input = "&&";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::And))));
+    }
+
+    #[test]
+    fn lone_ampersand_is_unknown_in_operator_mode() {
+        let input = "&";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::UnknownCharacter('&')))));
+    }
+
+    #[test]
+    fn semicolon_token() {
+        let input = ";";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Semicolon))));
+    }
+
+    #[test]
+    fn pipe_ampersand_and_semicolon_are_plain_word_characters_outside_operator_mode() {
+        let input = "a|b&c;d";
+        let mut lexer = Lexer::new(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Word(input)))));
+    }
+
+    #[test]
+    fn greater_token() {
+        let input = ">";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Greater))));
+    }
+
+    #[test]
+    fn greater_greater_token() {
+        let input = ">>";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::GreaterGreater))));
+    }
+
+    #[test]
+    fn less_token() {
+        let input = "<";
+        let mut lexer = Lexer::with_operators(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Less))));
+    }
+
+    #[test]
+    fn redirection_operators_split_a_fused_word_outside_quotes() {
+        let input = "out.txt>other.txt";
+        let mut lexer = Lexer::with_operators(input);
+        assert_eq!(lexer.next(), Some(Ok((0, Token::Word("out.txt")))));
+        assert_eq!(lexer.next(), Some(Ok((7, Token::Greater))));
+        assert_eq!(lexer.next(), Some(Ok((8, Token::Word("other.txt")))));
+    }
+
+    #[test]
+    fn greater_and_less_are_plain_word_characters_outside_operator_mode() {
+        let input = "a>b<c";
+        let mut lexer = Lexer::new(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Word(input)))));
+    }
+
+    #[test]
+    fn command_substitution_is_kept_whole() {
+        let input = "$(echo a; echo b)";
+        let mut lexer = Lexer::new(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Word(input)))));
+    }
+
+    #[test]
+    fn nested_command_substitution_is_kept_whole() {
+        let input = "$(echo $(whoami))";
+        let mut lexer = Lexer::new(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Word(input)))));
+    }
+
+    #[test]
+    fn command_substitution_continues_surrounding_word() {
+        let input = "a$(b|c)d";
+        let mut lexer = Lexer::new(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Ok((0, Token::Word(input)))));
+    }
+
+    #[test]
+    fn unterminated_command_substitution_is_an_error() {
+        let input = "$(echo a";
+        let mut lexer = Lexer::new(input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput(0))));
+    }
+
+    #[test]
+    fn command_substitution_too_deep_is_an_error() {
+        let input: String = (0..40).map(|_| "$(").collect::<String>() + &(0..40).map(|_| ")").collect::<String>();
+        let mut lexer = Lexer::new(&input);
+        let output = lexer.next();
+        assert_eq!(output, Some(Err(Error::SubstitutionTooDeep(0))));
     }
 
     #[bench]
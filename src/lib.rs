@@ -38,23 +38,36 @@
 //! returned. For other cases, it is possible to create the [Parser](Parser) manually and iterate over the parsed
 //! arguments.
 
+mod command;
+mod expand;
 mod lexer;
 mod parser;
+mod segment;
+mod substitute;
 
-pub use parser::Parser;
+pub use command::{Command, CommandParser, Redirection};
+pub use expand::ExpandingParser;
+pub use parser::{Parser, SpannedParser, UnescapingParser};
+pub use substitute::SubstitutingParser;
+use std::borrow::Cow;
 use thiserror::Error;
 
 /// The possible error returned from the parser.
 #[derive(Debug, Error, Copy, Clone, Eq, PartialEq)]
 pub enum Error<'a> {
     /// The input string ended unexpectedly (e.g. there is an unterminated quote or nothing after an escape sequence
-    /// starting `\`-character).
-    #[error("Unexpected end of input")]
-    UnexpectedEndOfInput,
+    /// starting `\`-character). The error wraps the index in the input string where the unterminated construct
+    /// began.
+    #[error("Unexpected end of input, in construct starting at index {0}")]
+    UnexpectedEndOfInput(usize),
     /// An unexpected token was encountered in the input string (e.g. an unbalanced quote in the middle or at the end
     /// of a word). The error wraps the token's index in the input string and its string value.
     #[error("Unexpected token in input at index {0}: {1}")]
     UnexpectedToken(usize, &'a str),
+    /// A `$(...)` command substitution starting at the given index nested more levels deep than the parser is
+    /// willing to follow, guarding against unbounded recursion on adversarial input.
+    #[error("Command substitution starting at index {0} is nested too deeply")]
+    SubstitutionTooDeep(usize),
 }
 
 /// Split a given input string into arguments, returning the first encountered error, if any. There may be valid
@@ -64,6 +77,12 @@ pub fn split(input: &str) -> Result<Vec<&str>, Error> {
     Parser::new(input).collect()
 }
 
+/// Split a given input string into arguments with escape sequences resolved, returning the first encountered
+/// error, if any. See [Parser::with_escapes](Parser::with_escapes) for the escape sequences that are resolved.
+pub fn split_unescaped(input: &str) -> Result<Vec<Cow<str>>, Error> {
+    Parser::with_escapes(input).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,7 +98,7 @@ mod tests {
     fn invalid_split_unexpected_eoi() {
         let input = "a \"b c";
         let output = split(input);
-        assert_eq!(output, Err(Error::UnexpectedEndOfInput));
+        assert_eq!(output, Err(Error::UnexpectedEndOfInput(2)));
     }
 
     #[test]
@@ -88,4 +107,18 @@ mod tests {
         let output = split(input);
         assert_eq!(output, Err(Error::UnexpectedToken(5, "\"")));
     }
+
+    #[test]
+    fn valid_split_unescaped() {
+        let input = "a \"b\\tc\"";
+        let output = split_unescaped(input);
+        assert_eq!(output, Ok(vec![Cow::Borrowed("a"), Cow::Owned("b\tc".to_string())]));
+    }
+
+    #[test]
+    fn split_treats_pipe_ampersand_and_semicolon_as_plain_characters() {
+        assert_eq!(split("a|b"), Ok(vec!["a|b"]));
+        assert_eq!(split("a;b"), Ok(vec!["a;b"]));
+        assert_eq!(split("a&&b"), Ok(vec!["a&&b"]));
+    }
 }
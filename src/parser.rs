@@ -1,7 +1,10 @@
 use crate::{
+    expand::ExpandingParser,
     lexer::{Lexer, Token},
+    substitute::SubstitutingParser,
     Error,
 };
+use std::{borrow::Cow, ops::Range};
 
 /// Iterator over the arguments in an input string.
 ///
@@ -48,19 +51,59 @@ impl<'a> Parser<'a> {
             lexer: Lexer::new(input),
         }
     }
-}
 
-impl<'a> Iterator for Parser<'a> {
-    type Item = Result<&'a str, Error<'a>>;
+    /// Return an [UnescapingParser](UnescapingParser) over a given input string.
+    ///
+    /// Unlike [Parser](Parser), escape sequences are resolved rather than kept verbatim, so the returned
+    /// arguments are `Cow<str>` instead of `&str`: an argument with no escapes stays a zero-copy
+    /// [Cow::Borrowed](std::borrow::Cow::Borrowed), while one containing an escape allocates a
+    /// [Cow::Owned](std::borrow::Cow::Owned).
+    pub fn with_escapes(input: &'a str) -> UnescapingParser<'a> {
+        UnescapingParser::new(input)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Wrap this parser to substitute `$NAME` and `${NAME...}` parameter references inside unquoted words and
+    /// double-quoted strings against `resolve`, leaving single-quoted text untouched. See
+    /// [ExpandingParser](ExpandingParser) for the supported brace forms and an example.
+    pub fn with_expansion<F>(self, resolve: F) -> ExpandingParser<'a, F>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        ExpandingParser::new(self.input, resolve)
+    }
+
+    /// Wrap this parser to resolve `$(...)` command substitutions against `substitute`, which receives the
+    /// substitution's inner source (with any substitutions nested inside it already resolved) and returns its
+    /// replacement output, or `None` to leave the `$(...)` span untouched. See
+    /// [SubstitutingParser](SubstitutingParser) for an example.
+    pub fn with_substitution<F>(self, substitute: F) -> SubstitutingParser<'a, F>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        SubstitutingParser::new(self.input, substitute)
+    }
+
+    /// Wrap this parser to additionally return each argument's byte range in the original input. See
+    /// [SpannedParser](SpannedParser) for details and an example.
+    pub fn spanned(self) -> SpannedParser<'a> {
+        SpannedParser::new(self.input)
+    }
+
+    /// Parse the next argument along with which quoting (if any) produced it and its full byte range in the
+    /// input, including surrounding quotes when quoted. This is the one scanner behind [Parser](Parser)'s own
+    /// [Iterator] impl, [SpannedParser](SpannedParser), [UnescapingParser](UnescapingParser), and
+    /// [ExpandingParser](crate::ExpandingParser) — they differ only in whether/how they post-process the raw
+    /// text this returns, based on its [Quoting](Quoting).
+    pub(crate) fn next_quoted(&mut self) -> Option<QuotedResult<'a>> {
         loop {
             break match self.lexer.next() {
                 Some(Ok((idx, token))) => match token {
                     Token::Whitespace(_) => continue,
                     Token::Word(_) | Token::UnknownCharacter(_) | Token::Escape(_) => loop {
                         match self.lexer.next() {
-                            Some(Ok((cont, Token::Whitespace(_)))) => break Some(Ok(&self.input[(idx..cont)])),
+                            Some(Ok((cont, Token::Whitespace(_)))) => {
+                                break Some(Ok((Quoting::Bare, &self.input[idx..cont], idx..cont)))
+                            }
                             Some(Ok((_, Token::Word(_))))
                             | Some(Ok((_, Token::UnknownCharacter(_))))
                             | Some(Ok((_, Token::Escape(_)))) => continue,
@@ -68,25 +111,171 @@ impl<'a> Iterator for Parser<'a> {
                                 break Some(Err(Error::UnexpectedToken(cont, &self.input[cont..cont + token.len()])))
                             }
                             Some(Err(e)) => break Some(Err(e)),
-                            None => break Some(Ok(&self.input[(idx..)])),
+                            None => break Some(Ok((Quoting::Bare, &self.input[idx..], idx..self.input.len()))),
                         }
                     },
                     Token::SingleQuote | Token::DoubleQuote => loop {
                         match self.lexer.next() {
-                            Some(Ok((cont, quote))) if quote == token => break Some(Ok(&self.input[idx + 1..cont])),
+                            Some(Ok((cont, quote))) if quote == token => {
+                                let quoting = if token == Token::SingleQuote { Quoting::Single } else { Quoting::Double };
+                                break Some(Ok((quoting, &self.input[idx + 1..cont], idx..cont + 1)))
+                            }
                             Some(Ok((_, _))) => continue,
-                            Some(Err(Error::UnexpectedEndOfInput)) | None => {
-                                break Some(Err(Error::UnexpectedEndOfInput))
+                            Some(Err(Error::UnexpectedEndOfInput(_))) | None => {
+                                break Some(Err(Error::UnexpectedEndOfInput(idx)))
                             }
                             Some(Err(e)) => break Some(Err(e)),
                         }
                     },
+                    Token::Pipe | Token::Or | Token::And | Token::Semicolon | Token::Greater | Token::GreaterGreater | Token::Less => {
+                        Some(Err(Error::UnexpectedToken(idx, &self.input[idx..idx + token.len()])))
+                    }
                 },
                 Some(Err(e)) => Some(Err(e)),
                 None => None,
             };
         }
     }
+
+    /// Parse the next argument along with its full byte range in the input, dropping the [Quoting](Quoting) that
+    /// [next_quoted](Parser::next_quoted) reports. Shared by [Parser](Parser)'s own [Iterator] impl and by
+    /// [SpannedParser](SpannedParser).
+    fn next_spanned(&mut self) -> Option<Result<(&'a str, Range<usize>), Error<'a>>> {
+        self.next_quoted().map(|r| r.map(|(_, text, range)| (text, range)))
+    }
+}
+
+/// The item type of [Parser::next_quoted](Parser::next_quoted): a span's [Quoting](Quoting), its raw text, and
+/// its full byte range in the input.
+pub(crate) type QuotedResult<'a> = Result<(Quoting, &'a str, Range<usize>), Error<'a>>;
+
+/// Which quoting (if any) produced a span returned by [Parser::next_quoted](Parser::next_quoted), used by
+/// wrappers to decide whether to post-process its raw text: single-quoted text is always left literal, while bare
+/// and double-quoted text may still contain escapes/expansions to resolve.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum Quoting {
+    /// A bare word, with no surrounding quotes.
+    Bare,
+    /// `'...'`: always literal.
+    Single,
+    /// `"..."`: may still contain escapes/expansions.
+    Double,
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Result<&'a str, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_spanned().map(|r| r.map(|(arg, _)| arg))
+    }
+}
+
+/// Iterator over the arguments in an input string alongside each argument's byte range in the original input.
+///
+/// Returned by [Parser::spanned](Parser::spanned). The range covers the argument's full extent in the input,
+/// including its surrounding quotes when quoted, which lets editor-style tooling (syntax highlighting, error
+/// underlining, re-editing command lines) map a parsed argument back to its source location.
+///
+/// ```rust
+/// # use shtring::Parser;
+/// let input = "a \"b c\"";
+/// let mut parser = Parser::new(input).spanned();
+/// assert_eq!(parser.next(), Some(Ok(("a", 0..1))));
+/// assert_eq!(parser.next(), Some(Ok(("b c", 2..7))));
+/// assert_eq!(parser.next(), None);
+/// ```
+#[derive(Debug)]
+pub struct SpannedParser<'a> {
+    parser: Parser<'a>,
+}
+
+impl<'a> SpannedParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { parser: Parser::new(input) }
+    }
+}
+
+impl<'a> Iterator for SpannedParser<'a> {
+    type Item = Result<(&'a str, Range<usize>), Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next_spanned()
+    }
+}
+
+/// Iterator over the arguments in an input string, resolving escape sequences as it parses.
+///
+/// Returned by [Parser::with_escapes](Parser::with_escapes). The individual returned items for an input string
+/// `&'a str` are `Result<Cow<'a, str>, Error>`. Escape sequences in the format `\<character>` are resolved inside
+/// unquoted words and double-quoted strings, according to the following table, mirroring common shell/lexer
+/// conventions. Single-quoted text is left untouched, matching the same single-quote-is-literal shell semantics
+/// as [ExpandingParser](crate::ExpandingParser).
+///
+/// | Escape | Resolves to |
+/// |--------|-------------|
+/// | `\n`   | newline     |
+/// | `\t`   | tab         |
+/// | `\\`   | `\`         |
+/// | `\"`   | `"`         |
+/// | `\'`   | `'`         |
+/// | `\<other>` | `<other>` |
+///
+/// ```rust
+/// # use shtring::Parser;
+/// # use std::borrow::Cow;
+/// let input = "a \"b\\tc\" \\\"d '\\t'";
+/// let mut parser = Parser::with_escapes(input);
+/// assert_eq!(parser.next(), Some(Ok(Cow::Borrowed("a"))));
+/// assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("b\tc".to_string()))));
+/// assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("\"d".to_string()))));
+/// assert_eq!(parser.next(), Some(Ok(Cow::Borrowed("\\t"))));
+/// assert_eq!(parser.next(), None);
+/// ```
+#[derive(Debug)]
+pub struct UnescapingParser<'a> {
+    parser: Parser<'a>,
+}
+
+impl<'a> UnescapingParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { parser: Parser::new(input) }
+    }
+}
+
+impl<'a> Iterator for UnescapingParser<'a> {
+    type Item = Result<Cow<'a, str>, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next_quoted().map(|r| {
+            r.map(|(quoting, raw, _)| match quoting {
+                Quoting::Single => Cow::Borrowed(raw),
+                Quoting::Bare | Quoting::Double => unescape(raw),
+            })
+        })
+    }
+}
+
+/// Resolve escape sequences in a single argument, borrowing the input where possible.
+fn unescape(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut resolved = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            resolved.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => resolved.push('\n'),
+            Some('t') => resolved.push('\t'),
+            Some(other) => resolved.push(other),
+            None => {}
+        }
+    }
+    Cow::Owned(resolved)
 }
 
 #[cfg(test)]
@@ -149,7 +338,7 @@ mod tests {
         let input = "'a";
         let mut parser = Parser::new(input);
         let output = parser.next();
-        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput)));
+        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput(0))));
     }
 
     #[test]
@@ -157,7 +346,7 @@ mod tests {
         let input = "\"a";
         let mut parser = Parser::new(input);
         let output = parser.next();
-        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput)));
+        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput(0))));
     }
 
     #[test]
@@ -165,7 +354,7 @@ mod tests {
         let input = "\"a'";
         let mut parser = Parser::new(input);
         let output = parser.next();
-        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput)));
+        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput(0))));
     }
 
     #[test]
@@ -185,4 +374,70 @@ mod tests {
             assert_eq!(output, vec![Ok("a"), Ok("b \\\"c d"), Ok("e"), Ok("f g")]);
         });
     }
+
+    #[test]
+    fn unescaping_word_without_escapes_is_borrowed() {
+        let input = "a";
+        let mut parser = Parser::with_escapes(input);
+        let output = parser.next();
+        assert!(matches!(output, Some(Ok(Cow::Borrowed(_)))));
+        assert_eq!(output, Some(Ok(Cow::Borrowed("a"))));
+    }
+
+    #[test]
+    fn unescaping_word_with_escapes_is_owned() {
+        let input = "a\\tb\\nc\\\\d\\\"e\\'f";
+        let mut parser = Parser::with_escapes(input);
+        let output = parser.next();
+        assert!(matches!(output, Some(Ok(Cow::Owned(_)))));
+        assert_eq!(output, Some(Ok(Cow::<str>::Owned("a\tb\nc\\d\"e'f".to_string()))));
+    }
+
+    #[test]
+    fn unescaping_quoted_word_with_escapes() {
+        let input = "\"a\\tb\"";
+        let mut parser = Parser::with_escapes(input);
+        let output = parser.next();
+        assert_eq!(output, Some(Ok(Cow::<str>::Owned("a\tb".to_string()))));
+    }
+
+    #[test]
+    fn unescaping_single_quoted_word_is_left_literal() {
+        let input = "'a\\tb'";
+        let mut parser = Parser::with_escapes(input);
+        let output = parser.next();
+        assert_eq!(output, Some(Ok(Cow::Borrowed("a\\tb"))));
+    }
+
+    #[test]
+    fn bare_pipe_is_a_plain_word_character() {
+        let input = "|";
+        let mut parser = Parser::new(input);
+        let output = parser.next();
+        assert_eq!(output, Some(Ok("|")));
+    }
+
+    #[test]
+    fn unescaping_unterminated_quote_is_still_an_error() {
+        let input = "\"a";
+        let mut parser = Parser::with_escapes(input);
+        let output = parser.next();
+        assert_eq!(output, Some(Err(Error::UnexpectedEndOfInput(0))));
+    }
+
+    #[test]
+    fn spanned_words_and_quotes() {
+        let input = "a \"b c\"";
+        let parser = Parser::new(input).spanned();
+        let output: Vec<Result<(&str, std::ops::Range<usize>), Error>> = parser.collect();
+        assert_eq!(output, vec![Ok(("a", 0..1)), Ok(("b c", 2..7))]);
+    }
+
+    #[test]
+    fn spanned_unterminated_quote_reports_its_own_start() {
+        let input = "a \"b";
+        let mut parser = Parser::new(input).spanned();
+        assert_eq!(parser.next(), Some(Ok(("a", 0..1))));
+        assert_eq!(parser.next(), Some(Err(Error::UnexpectedEndOfInput(2))));
+    }
 }
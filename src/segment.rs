@@ -0,0 +1,184 @@
+//! A small word-segment AST used to split a word into literal text and `$NAME`/`${NAME...}` parameter references,
+//! so that [expand](crate::expand) can substitute the latter while leaving the former untouched.
+
+/// A single piece of a word after splitting out parameter references from literal text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum WordSegment<'a> {
+    Literal(&'a str),
+    Parameter(Parameter<'a>),
+}
+
+/// A `$NAME` or `${NAME...}` reference inside a word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Parameter<'a> {
+    pub name: &'a str,
+    pub format: Option<ParameterFormat<'a>>,
+}
+
+/// The `:-`, `:=`, `:+`, `:?` brace forms of a parameter reference, each carrying the default/alternate/error
+/// `Word` that follows the operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ParameterFormat<'a> {
+    /// `${NAME:-default}`: substitute `default` if `NAME` is unresolved.
+    Default(Word<'a>),
+    /// `${NAME:=assign}`: substitute `assign` if `NAME` is unresolved.
+    Assign(Word<'a>),
+    /// `${NAME:+alternate}`: substitute `alternate` if `NAME` is resolved, else nothing.
+    Alternate(Word<'a>),
+    /// `${NAME:?error}`: substitute `error` if `NAME` is unresolved.
+    Error(Word<'a>),
+}
+
+/// A word made up of literal and parameter segments, e.g. `prefix-${NAME:-default}-suffix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Word<'a>(pub Vec<WordSegment<'a>>);
+
+/// Splits a word into [WordSegment](WordSegment)s, recognising `$NAME` and `${NAME...}` parameter references.
+#[derive(Debug)]
+pub(crate) struct SegmentLexer<'a> {
+    rest: &'a str,
+}
+
+impl<'a> SegmentLexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { rest: input }
+    }
+
+    pub fn parse(mut self) -> Word<'a> {
+        let mut segments = Vec::new();
+        while !self.rest.is_empty() {
+            match self.rest.find('$') {
+                None => {
+                    segments.push(WordSegment::Literal(self.rest));
+                    self.rest = "";
+                }
+                Some(0) => {
+                    if let Some((parameter, consumed)) = parse_parameter(self.rest) {
+                        segments.push(WordSegment::Parameter(parameter));
+                        self.rest = &self.rest[consumed..];
+                    } else {
+                        segments.push(WordSegment::Literal(&self.rest[..1]));
+                        self.rest = &self.rest[1..];
+                    }
+                }
+                Some(pos) => {
+                    segments.push(WordSegment::Literal(&self.rest[..pos]));
+                    self.rest = &self.rest[pos..];
+                }
+            }
+        }
+        Word(segments)
+    }
+}
+
+/// Parse a single `$NAME` or `${NAME...}` reference starting at `input[0] == '$'`, returning the parameter and
+/// the number of bytes it consumed from `input`.
+fn parse_parameter(input: &str) -> Option<(Parameter<'_>, usize)> {
+    let rest = &input[1..];
+    if let Some(body) = rest.strip_prefix('{') {
+        let end = find_matching_brace(body)?;
+        let (name, format) = parse_braced(&body[..end]);
+        Some((Parameter { name, format }, 2 + end + 1))
+    } else {
+        let end = rest.find(|c: char| !is_name_character(c)).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        Some((Parameter { name: &rest[..end], format: None }, 1 + end))
+    }
+}
+
+/// Find the index (relative to `body`) of the `}` that closes the `{` this body is nested inside, treating
+/// further `{`/`}` pairs as nested so that e.g. `${NAME:-${OTHER}}` closes on the outer brace.
+fn find_matching_brace(body: &str) -> Option<usize> {
+    let mut depth = 1;
+    for (idx, c) in body.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split the text between `{` and `}` into a parameter name and an optional `:-`/`:=`/`:+`/`:?` format.
+fn parse_braced(inner: &str) -> (&str, Option<ParameterFormat<'_>>) {
+    match inner.find(':') {
+        Some(pos) if pos + 1 < inner.len() => {
+            let name = &inner[..pos];
+            let default = SegmentLexer::new(&inner[pos + 2..]).parse();
+            match &inner[pos + 1..pos + 2] {
+                "-" => (name, Some(ParameterFormat::Default(default))),
+                "=" => (name, Some(ParameterFormat::Assign(default))),
+                "+" => (name, Some(ParameterFormat::Alternate(default))),
+                "?" => (name, Some(ParameterFormat::Error(default))),
+                _ => (inner, None),
+            }
+        }
+        _ => (inner, None),
+    }
+}
+
+fn is_name_character(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_only() {
+        let word = SegmentLexer::new("hello").parse();
+        assert_eq!(word, Word(vec![WordSegment::Literal("hello")]));
+    }
+
+    #[test]
+    fn bare_parameter() {
+        let word = SegmentLexer::new("$NAME").parse();
+        assert_eq!(word, Word(vec![WordSegment::Parameter(Parameter { name: "NAME", format: None })]));
+    }
+
+    #[test]
+    fn parameter_surrounded_by_literal_text() {
+        let word = SegmentLexer::new("a$NAME b").parse();
+        assert_eq!(
+            word,
+            Word(vec![
+                WordSegment::Literal("a"),
+                WordSegment::Parameter(Parameter { name: "NAME", format: None }),
+                WordSegment::Literal(" b"),
+            ])
+        );
+    }
+
+    #[test]
+    fn braced_parameter() {
+        let word = SegmentLexer::new("${NAME}").parse();
+        assert_eq!(word, Word(vec![WordSegment::Parameter(Parameter { name: "NAME", format: None })]));
+    }
+
+    #[test]
+    fn braced_parameter_with_default() {
+        let word = SegmentLexer::new("${NAME:-default}").parse();
+        assert_eq!(
+            word,
+            Word(vec![WordSegment::Parameter(Parameter {
+                name: "NAME",
+                format: Some(ParameterFormat::Default(Word(vec![WordSegment::Literal("default")]))),
+            })])
+        );
+    }
+
+    #[test]
+    fn dollar_with_no_valid_name_is_literal() {
+        let word = SegmentLexer::new("$ $").parse();
+        assert_eq!(word, Word(vec![WordSegment::Literal("$"), WordSegment::Literal(" "), WordSegment::Literal("$")]));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{parser::Parser, Error};
+use std::borrow::Cow;
+
+/// Iterator over the arguments in an input string, resolving `$(...)` command substitutions against a
+/// caller-supplied callback as it parses.
+///
+/// Returned by [Parser::with_substitution](crate::Parser::with_substitution). The [Lexer](crate::lexer::Lexer)
+/// already keeps a `$(...)` span intact (tracking nested `$(` so an inner `)` doesn't close the outer
+/// substitution), so this wrapper only needs to locate those spans in each argument and hand their inner source
+/// to `substitute`, innermost first. If `substitute` returns `None` the span is left untouched, verbatim.
+///
+/// ```rust
+/// # use shtring::Parser;
+/// # use std::borrow::Cow;
+/// let input = "echo $(whoami)";
+/// let substitute = |command: &str| match command {
+///     "whoami" => Some("root".to_string()),
+///     _ => None,
+/// };
+/// let mut parser = Parser::new(input).with_substitution(substitute);
+/// assert_eq!(parser.next(), Some(Ok(Cow::Borrowed("echo"))));
+/// assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("root".to_string()))));
+/// assert_eq!(parser.next(), None);
+/// ```
+#[derive(Debug)]
+pub struct SubstitutingParser<'a, F> {
+    parser: Parser<'a>,
+    substitute: F,
+}
+
+impl<'a, F> SubstitutingParser<'a, F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    pub(crate) fn new(input: &'a str, substitute: F) -> Self {
+        Self { parser: Parser::new(input), substitute }
+    }
+}
+
+impl<'a, F> Iterator for SubstitutingParser<'a, F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    type Item = Result<Cow<'a, str>, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next().map(|arg| arg.map(|raw| substitute(raw, &self.substitute)))
+    }
+}
+
+/// Resolve every top-level `$(...)` substitution in `raw`, borrowing the input where possible. Nested
+/// substitutions are resolved first so `substitute` always sees an inner source with its own `$(...)` spans
+/// already replaced.
+fn substitute<'a>(raw: &'a str, resolve: &impl Fn(&str) -> Option<String>) -> Cow<'a, str> {
+    let Some(start) = raw.find("$(") else {
+        return Cow::Borrowed(raw);
+    };
+    let Some(close) = find_matching_paren(raw, start + 1) else {
+        return Cow::Borrowed(raw);
+    };
+
+    let mut resolved = String::with_capacity(raw.len());
+    resolved.push_str(&raw[..start]);
+    let inner = substitute(&raw[start + 2..close], resolve);
+    if let Some(output) = resolve(&inner) {
+        resolved.push_str(&output);
+    } else {
+        resolved.push_str(&raw[start..close + 1]);
+    }
+    resolved.push_str(&substitute(&raw[close + 1..], resolve));
+    Cow::Owned(resolved)
+}
+
+/// Find the index of the `)` matching the `(` at `open`, tracking nested parens and skipping backslash-escaped
+/// characters. Returns `None` if the parens are unbalanced. The lexer pairs only `$(` with `)`, so a bare `(`
+/// inside a substitution (e.g. `$(a (b)`) can leave this count unclosed even for input the lexer accepted.
+fn find_matching_paren(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut chars = s[open + 1..].char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + 1 + idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    fn resolve(command: &str) -> Option<String> {
+        match command {
+            "whoami" => Some("root".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn word_without_substitution_is_borrowed() {
+        let input = "hello";
+        let mut parser = Parser::new(input).with_substitution(resolve);
+        let output = parser.next();
+        assert!(matches!(output, Some(Ok(Cow::Borrowed(_)))));
+        assert_eq!(output, Some(Ok(Cow::Borrowed("hello"))));
+    }
+
+    #[test]
+    fn resolved_substitution_replaces_span() {
+        let input = "$(whoami)";
+        let mut parser = Parser::new(input).with_substitution(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("root".to_string()))));
+    }
+
+    #[test]
+    fn substitution_embedded_in_word_keeps_surrounding_text() {
+        let input = "user:$(whoami):end";
+        let mut parser = Parser::new(input).with_substitution(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("user:root:end".to_string()))));
+    }
+
+    #[test]
+    fn unresolved_substitution_is_left_verbatim() {
+        let input = "$(unknown)";
+        let mut parser = Parser::new(input).with_substitution(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("$(unknown)".to_string()))));
+    }
+
+    #[test]
+    fn nested_substitution_resolves_innermost_first() {
+        let input = "$(echo $(whoami))";
+        let resolve = |command: &str| match command {
+            "whoami" => Some("root".to_string()),
+            "echo root" => Some("root".to_string()),
+            _ => None,
+        };
+        let mut parser = Parser::new(input).with_substitution(resolve);
+        assert_eq!(parser.next(), Some(Ok(Cow::<str>::Owned("root".to_string()))));
+    }
+}